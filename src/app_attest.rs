@@ -0,0 +1,447 @@
+// Copyright 2020 Bryant Luk
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! App Attest lets a device prove its app integrity using hardware-backed
+//! keys, and is the successor to [`crate::device_check`].
+//!
+//! See [official documentation][apple_docs].
+//!
+//! [apple_docs]: https://developer.apple.com/documentation/devicecheck/validating_apps_that_connect_to_your_server
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::{string::String, vec::Vec};
+
+use crate::crypto::Jwk;
+use serde::Deserialize;
+
+/// The attestation statement format App Attest uses.
+const ATTESTATION_FORMAT: &str = "apple-appattest";
+
+/// The OID (in `{ arc, arc, ... }` form) of the X.509 extension on the
+/// credential certificate that carries the attestation nonce.
+const APPLE_NONCE_EXTENSION_OID: &[u64] = &[1, 2, 840, 113635, 100, 8, 2];
+
+/// A credential public key and its current signature counter, produced by
+/// [`verify_attestation`]. [`verify_assertion`] returns only the new counter.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg(feature = "std")]
+pub struct AttestedCredential {
+    /// The credential's public key, as an EC P-256 [`Jwk`].
+    pub public_key: Jwk,
+    /// The authenticator's signature counter.
+    pub counter: u32,
+}
+
+/// Errors which can occur while verifying an attestation or assertion object.
+#[derive(Debug)]
+#[non_exhaustive]
+#[cfg(feature = "std")]
+pub enum Error {
+    /// The attestation or assertion object was not valid CBOR.
+    Cbor(ciborium::de::Error<std::io::Error>),
+    /// `fmt` was not `apple-appattest`.
+    UnsupportedFormat,
+    /// `attStmt.x5c` did not contain a credential certificate.
+    MissingCredCert,
+    /// A certificate in the chain could not be parsed.
+    InvalidCertificate,
+    /// The certificate chain did not verify up to the Apple App Attest Root CA.
+    InvalidChain,
+    /// The credential certificate was missing the nonce extension, or its
+    /// contents did not match the computed nonce.
+    InvalidNonce,
+    /// `authData` was truncated or malformed.
+    InvalidAuthData,
+    /// `authData.rpIdHash` did not match `SHA256(appId)`.
+    InvalidRpIdHash,
+    /// The attestation's signature counter was not `0`.
+    InvalidInitialCounter,
+    /// The attested credential id did not match `SHA256(publicKey)`.
+    InvalidCredentialId,
+    /// The assertion signature did not verify.
+    InvalidSignature,
+    /// The assertion's counter did not advance past the last known counter.
+    CounterDidNotIncrease,
+    /// A certificate in the chain was not valid (per its `notBefore`/
+    /// `notAfter`) at the time passed to [`verify_attestation`].
+    CertificateNotValid,
+}
+
+#[derive(Deserialize)]
+struct AttestationObject {
+    fmt: String,
+    #[serde(rename = "attStmt")]
+    att_stmt: AttestationStatement,
+    #[serde(rename = "authData")]
+    auth_data: serde_bytes::ByteBuf,
+}
+
+#[derive(Deserialize)]
+struct AttestationStatement {
+    x5c: Vec<serde_bytes::ByteBuf>,
+}
+
+#[derive(Deserialize)]
+struct AssertionObject {
+    signature: serde_bytes::ByteBuf,
+    #[serde(rename = "authenticatorData")]
+    authenticator_data: serde_bytes::ByteBuf,
+}
+
+/// The parsed fixed-size header of an `authData` buffer.
+struct AuthData<'a> {
+    rp_id_hash: &'a [u8],
+    counter: u32,
+    attested_credential: Option<AttestedCredentialData<'a>>,
+}
+
+struct AttestedCredentialData<'a> {
+    credential_id: &'a [u8],
+    credential_public_key: &'a [u8],
+}
+
+fn parse_auth_data(auth_data: &[u8]) -> Result<AuthData<'_>, Error> {
+    if auth_data.len() < 37 {
+        return Err(Error::InvalidAuthData);
+    }
+    let rp_id_hash = &auth_data[..32];
+    let flags = auth_data[32];
+    let counter = u32::from_be_bytes(
+        auth_data[33..37]
+            .try_into()
+            .map_err(|_| Error::InvalidAuthData)?,
+    );
+
+    // Bit 6 (0x40) is the "attested credential data included" flag.
+    let attested_credential = if flags & 0x40 != 0 {
+        let rest = &auth_data[37..];
+        if rest.len() < 16 + 2 {
+            return Err(Error::InvalidAuthData);
+        }
+        let credential_id_len = u16::from_be_bytes(
+            rest[16..18].try_into().map_err(|_| Error::InvalidAuthData)?,
+        ) as usize;
+        let rest = &rest[18..];
+        if rest.len() < credential_id_len {
+            return Err(Error::InvalidAuthData);
+        }
+        let credential_id = &rest[..credential_id_len];
+        let credential_public_key = &rest[credential_id_len..];
+        Some(AttestedCredentialData {
+            credential_id,
+            credential_public_key,
+        })
+    } else {
+        None
+    };
+
+    Ok(AuthData {
+        rp_id_hash,
+        counter,
+        attested_credential,
+    })
+}
+
+/// Decodes a COSE_Key CBOR map into an EC P-256 [`Jwk`].
+fn jwk_from_cose_key(cose_key: &[u8]) -> Result<Jwk, Error> {
+    let value: ciborium::value::Value =
+        ciborium::de::from_reader(cose_key).map_err(Error::Cbor)?;
+    let map = value.as_map().ok_or(Error::InvalidAuthData)?;
+
+    let find = |key: i32| -> Option<&[u8]> {
+        map.iter().find_map(|(k, v)| {
+            if k.as_integer() == Some(key.into()) {
+                v.as_bytes().map(Vec::as_slice)
+            } else {
+                None
+            }
+        })
+    };
+
+    let x = find(-2).ok_or(Error::InvalidAuthData)?;
+    let y = find(-3).ok_or(Error::InvalidAuthData)?;
+
+    Ok(Jwk {
+        kty: String::from("EC"),
+        r#use: None,
+        alg: None,
+        kid: None,
+        crv: Some(String::from("P-256")),
+        x: Some(crate::base64url::encode(x)),
+        y: Some(crate::base64url::encode(y)),
+        e: None,
+        n: None,
+    })
+}
+
+fn sha256(data: &[u8]) -> [u8; 32] {
+    let digest = ring::digest::digest(&ring::digest::SHA256, data);
+    let mut out = [0u8; 32];
+    out.copy_from_slice(digest.as_ref());
+    out
+}
+
+/// Verifies that the credential certificate's nonce extension (OID
+/// `1.2.840.113635.100.8.2`) carries `expected_nonce`.
+///
+/// The extension value is a DER `SEQUENCE` containing a single `OCTET
+/// STRING`, itself wrapping the 32 byte nonce.
+fn verify_nonce_extension(
+    cert: &x509_parser::certificate::X509Certificate<'_>,
+    expected_nonce: &[u8; 32],
+) -> Result<(), Error> {
+    let oid = x509_parser::oid_registry::asn1_rs::Oid::from(APPLE_NONCE_EXTENSION_OID)
+        .map_err(|_| Error::InvalidNonce)?;
+    let ext = cert
+        .get_extension_unique(&oid)
+        .map_err(|_| Error::InvalidNonce)?
+        .ok_or(Error::InvalidNonce)?;
+
+    let octet_string = extract_octet_string_from_der_sequence(ext.value)?;
+    if octet_string == expected_nonce {
+        Ok(())
+    } else {
+        Err(Error::InvalidNonce)
+    }
+}
+
+/// Unwraps a DER `SEQUENCE { OCTET STRING }` with `x509_parser`'s re-exported
+/// `der_parser` and returns the contents of the sequence's first element.
+fn extract_octet_string_from_der_sequence(value: &[u8]) -> Result<&[u8], Error> {
+    let (_, seq) =
+        x509_parser::der_parser::ber::parse_ber_sequence(value).map_err(|_| Error::InvalidNonce)?;
+    let inner = seq.as_sequence().map_err(|_| Error::InvalidNonce)?;
+    inner
+        .first()
+        .and_then(|el| el.as_slice().ok())
+        .ok_or(Error::InvalidNonce)
+}
+
+/// Verifies the CBOR attestation object a device produces during key
+/// attestation, returning the attested public key and initial (zero)
+/// counter on success.
+///
+/// `challenge` is the one-time server challenge the device was asked to
+/// attest. `app_id` is `"{team_id}.{bundle_id}"`. `apple_root_ca` is the DER
+/// encoding of Apple's App Attest Root CA certificate. `now` is checked
+/// against every certificate's validity period.
+#[cfg(feature = "std")]
+pub fn verify_attestation(
+    attestation_object: &[u8],
+    challenge: &[u8],
+    app_id: &str,
+    apple_root_ca: &[u8],
+    now: &impl crate::time::DurationSinceEpoch,
+) -> Result<AttestedCredential, Error> {
+    let attestation: AttestationObject =
+        ciborium::de::from_reader(attestation_object).map_err(Error::Cbor)?;
+
+    if attestation.fmt != ATTESTATION_FORMAT {
+        return Err(Error::UnsupportedFormat);
+    }
+
+    let cred_cert_der = attestation
+        .att_stmt
+        .x5c
+        .first()
+        .ok_or(Error::MissingCredCert)?;
+
+    let (_, cred_cert) = x509_parser::parse_x509_certificate(cred_cert_der)
+        .map_err(|_| Error::InvalidCertificate)?;
+
+    verify_chain(&attestation.att_stmt.x5c, apple_root_ca, now)?;
+
+    let client_data_hash = sha256(challenge);
+    let mut nonce_input = Vec::with_capacity(attestation.auth_data.len() + 32);
+    nonce_input.extend_from_slice(&attestation.auth_data);
+    nonce_input.extend_from_slice(&client_data_hash);
+    let nonce = sha256(&nonce_input);
+    verify_nonce_extension(&cred_cert, &nonce)?;
+
+    let auth_data = parse_auth_data(&attestation.auth_data)?;
+    let expected_rp_id_hash = sha256(app_id.as_bytes());
+    if auth_data.rp_id_hash != expected_rp_id_hash {
+        return Err(Error::InvalidRpIdHash);
+    }
+    if auth_data.counter != 0 {
+        return Err(Error::InvalidInitialCounter);
+    }
+
+    let attested = auth_data
+        .attested_credential
+        .ok_or(Error::InvalidAuthData)?;
+    let public_key = jwk_from_cose_key(attested.credential_public_key)?;
+    if attested.credential_id != sha256(attested.credential_public_key) {
+        return Err(Error::InvalidCredentialId);
+    }
+
+    Ok(AttestedCredential {
+        public_key,
+        counter: 0,
+    })
+}
+
+/// Verifies the X.509 chain `[cred_cert, intermediate, ...]` up to
+/// `apple_root_ca`, checking both signature linkage and that every
+/// certificate is within its validity period at `now`.
+fn verify_chain(
+    x5c: &[serde_bytes::ByteBuf],
+    apple_root_ca: &[u8],
+    now: &impl crate::time::DurationSinceEpoch,
+) -> Result<(), Error> {
+    let (_, root) =
+        x509_parser::parse_x509_certificate(apple_root_ca).map_err(|_| Error::InvalidChain)?;
+
+    let mut certs = Vec::with_capacity(x5c.len() + 1);
+    for der in x5c {
+        let (_, cert) =
+            x509_parser::parse_x509_certificate(der).map_err(|_| Error::InvalidCertificate)?;
+        certs.push(cert);
+    }
+    certs.push(root);
+
+    let now_secs = now.as_secs() as i64;
+    for cert in &certs {
+        let validity = cert.validity();
+        if now_secs < validity.not_before.timestamp() || now_secs > validity.not_after.timestamp()
+        {
+            return Err(Error::CertificateNotValid);
+        }
+    }
+
+    for pair in certs.windows(2) {
+        let (subject, issuer) = (&pair[0], &pair[1]);
+        subject
+            .verify_signature(Some(issuer.public_key()))
+            .map_err(|_| Error::InvalidChain)?;
+    }
+
+    Ok(())
+}
+
+/// Verifies a later assertion against a previously attested public key,
+/// returning the assertion's signature counter on success.
+///
+/// A counter that is not greater than `last_counter` is rejected with
+/// [`Error::CounterDidNotIncrease`], so on success the returned counter is
+/// always greater than `last_counter`.
+#[cfg(feature = "std")]
+pub fn verify_assertion(
+    assertion_object: &[u8],
+    client_data: &[u8],
+    app_id: &str,
+    public_key: &Jwk,
+    last_counter: u32,
+) -> Result<u32, Error> {
+    let assertion: AssertionObject =
+        ciborium::de::from_reader(assertion_object).map_err(Error::Cbor)?;
+
+    let client_data_hash = sha256(client_data);
+    let mut nonce_input = Vec::with_capacity(assertion.authenticator_data.len() + 32);
+    nonce_input.extend_from_slice(&assertion.authenticator_data);
+    nonce_input.extend_from_slice(&client_data_hash);
+    let nonce = sha256(&nonce_input);
+
+    let x = public_key
+        .x
+        .as_deref()
+        .map(crate::base64url::decode)
+        .transpose()
+        .map_err(|_| Error::InvalidAuthData)?
+        .ok_or(Error::InvalidAuthData)?;
+    let y = public_key
+        .y
+        .as_deref()
+        .map(crate::base64url::decode)
+        .transpose()
+        .map_err(|_| Error::InvalidAuthData)?
+        .ok_or(Error::InvalidAuthData)?;
+
+    let mut point = Vec::with_capacity(1 + x.len() + y.len());
+    point.push(0x04);
+    point.extend_from_slice(&x);
+    point.extend_from_slice(&y);
+
+    // App Attest assertions are signed with the authenticator's private key
+    // using the ASN.1 DER signature form, not the JOSE fixed form `jwt`
+    // signs with (see `crypto::ecdsa::EcdsaP256Sha256Asn1Format`).
+    let unparsed_key =
+        ring::signature::UnparsedPublicKey::new(&ring::signature::ECDSA_P256_SHA256_ASN1, &point);
+    unparsed_key
+        .verify(&nonce, &assertion.signature)
+        .map_err(|_| Error::InvalidSignature)?;
+
+    let auth_data = parse_auth_data(&assertion.authenticator_data)?;
+    let expected_rp_id_hash = sha256(app_id.as_bytes());
+    if auth_data.rp_id_hash != expected_rp_id_hash {
+        return Err(Error::InvalidRpIdHash);
+    }
+    if auth_data.counter <= last_counter {
+        return Err(Error::CounterDidNotIncrease);
+    }
+
+    Ok(auth_data.counter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_octet_string_from_der_sequence, parse_auth_data};
+
+    #[test]
+    fn parse_auth_data_with_attested_credential_data() {
+        let mut auth_data = Vec::new();
+        auth_data.extend_from_slice(&[0xAA; 32]); // rpIdHash
+        auth_data.push(0x40); // flags: attested credential data included
+        auth_data.extend_from_slice(&0u32.to_be_bytes()); // counter
+        auth_data.extend_from_slice(&[0xBB; 16]); // aaguid
+        auth_data.extend_from_slice(&4u16.to_be_bytes()); // credential id length
+        auth_data.extend_from_slice(&[0xCC; 4]); // credential id
+        auth_data.extend_from_slice(&[0xDD; 8]); // credential public key (rest of buffer)
+
+        let parsed = parse_auth_data(&auth_data).unwrap();
+        assert_eq!(parsed.rp_id_hash, &[0xAA; 32]);
+        assert_eq!(parsed.counter, 0);
+        let attested = parsed.attested_credential.unwrap();
+        assert_eq!(attested.credential_id, &[0xCC; 4]);
+        assert_eq!(attested.credential_public_key, &[0xDD; 8]);
+    }
+
+    #[test]
+    fn parse_auth_data_without_attested_credential_data() {
+        let mut auth_data = Vec::new();
+        auth_data.extend_from_slice(&[0x11; 32]); // rpIdHash
+        auth_data.push(0x00); // flags: no attested credential data
+        auth_data.extend_from_slice(&7u32.to_be_bytes()); // counter
+
+        let parsed = parse_auth_data(&auth_data).unwrap();
+        assert_eq!(parsed.rp_id_hash, &[0x11; 32]);
+        assert_eq!(parsed.counter, 7);
+        assert!(parsed.attested_credential.is_none());
+    }
+
+    #[test]
+    fn parse_auth_data_rejects_truncated_input() {
+        let auth_data = [0u8; 36];
+        assert!(matches!(
+            parse_auth_data(&auth_data),
+            Err(super::Error::InvalidAuthData)
+        ));
+    }
+
+    #[test]
+    fn extract_octet_string_from_der_sequence_unwraps_nonce() {
+        let nonce = [0x42u8; 32];
+        let mut der = vec![0x30, 0x22, 0x04, 0x20];
+        der.extend_from_slice(&nonce);
+
+        let extracted = extract_octet_string_from_der_sequence(&der).unwrap();
+        assert_eq!(extracted, &nonce);
+    }
+}
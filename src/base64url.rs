@@ -0,0 +1,32 @@
+// Copyright 2020 Bryant Luk
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Base64url (no padding) helpers used when assembling/parsing compact JWS
+//! serializations.
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::string::String;
+#[cfg(feature = "std")]
+use std::string::String;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+
+/// Encodes bytes as base64url without padding.
+pub(crate) fn encode(input: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(input)
+}
+
+/// Decodes a base64url (no padding) string into bytes.
+pub(crate) fn decode(input: &str) -> Result<Vec<u8>, base64::DecodeError> {
+    URL_SAFE_NO_PAD.decode(input)
+}
@@ -0,0 +1,408 @@
+// Copyright 2020 Bryant Luk
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! JWK/JWKS support for verifying RS256-signed tokens, such as the
+//! `id_token` Sign in with Apple returns.
+//!
+//! See [RFC 7517][rfc_7517].
+//!
+//! [rfc_7517]: https://tools.ietf.org/html/rfc7517
+
+use std::{string::String, vec::Vec};
+
+use serde::{Deserialize, Deserializer};
+
+fn deserialize_base64url<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = <&str>::deserialize(deserializer)?;
+    crate::base64url::decode(s).map_err(serde::de::Error::custom)
+}
+
+/// A JSON Web Key, with the RSA modulus/exponent already base64url-decoded.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize)]
+pub struct Jwk {
+    pub kty: String,
+    pub kid: String,
+    #[serde(default)]
+    pub alg: Option<String>,
+    #[serde(rename = "n", deserialize_with = "deserialize_base64url")]
+    pub n: Vec<u8>,
+    #[serde(rename = "e", deserialize_with = "deserialize_base64url")]
+    pub e: Vec<u8>,
+}
+
+/// A JSON Web Key set.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize)]
+pub struct JwkSet {
+    pub keys: Vec<Jwk>,
+}
+
+/// The audience of a verified token: either a single value or an array.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum OwnedOneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// The claims decoded from a token verified by [`verify`].
+///
+/// Unlike [`crate::jwt::Claims`], these fields own their storage, since they are
+/// deserialized from a buffer internal to [`verify`] rather than borrowed
+/// from the caller.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize)]
+pub struct VerifiedClaims {
+    pub iss: String,
+    pub iat: u64,
+    pub exp: Option<u64>,
+    pub nbf: Option<u64>,
+    pub sub: Option<String>,
+    #[serde(default)]
+    pub aud: Option<OwnedOneOrMany>,
+}
+
+#[derive(Deserialize)]
+struct TokenHeader {
+    kid: String,
+}
+
+/// Errors which can occur while verifying a token against a [`JwkSet`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The token was not a three segment compact JWS.
+    MalformedToken,
+    /// The header or claims could not be deserialized.
+    Json(serde_json::Error),
+    /// A segment was not valid base64url.
+    Base64(base64::DecodeError),
+    /// No JWK in the set matched the token's `kid`.
+    UnknownKey,
+    /// The signature did not verify.
+    InvalidSignature,
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+impl From<base64::DecodeError> for Error {
+    fn from(err: base64::DecodeError) -> Self {
+        Error::Base64(err)
+    }
+}
+
+/// Verifies an RS256-signed compact JWT against `jwks` and returns its
+/// claims.
+///
+/// The matching [`Jwk`] is selected by the token header's `kid`, and the
+/// signature is recomputed over `header.payload` with RSASSA-PKCS1-v1_5 /
+/// SHA-256.
+pub fn verify(token: &str, jwks: &JwkSet) -> Result<VerifiedClaims, Error> {
+    Ok(serde_json::from_slice(&verify_signature(token, jwks)?)?)
+}
+
+/// Verifies an RS256-signed compact JWT against `jwks` and returns its
+/// raw, still-encoded claims (payload) bytes.
+///
+/// This is the shared primitive behind [`verify`]: callers whose claims type
+/// isn't [`VerifiedClaims`] (e.g. [`crate::siwa::IdTokenClaims`], which
+/// carries Apple-specific fields like `c_hash`) can deserialize the returned
+/// bytes into their own claims type instead.
+///
+/// The matching [`Jwk`] is selected by the token header's `kid`, and the
+/// signature is recomputed over `header.payload` with RSASSA-PKCS1-v1_5 /
+/// SHA-256.
+pub fn verify_signature(token: &str, jwks: &JwkSet) -> Result<Vec<u8>, Error> {
+    let mut segments = token.split('.');
+    let header_segment = segments.next().ok_or(Error::MalformedToken)?;
+    let payload_segment = segments.next().ok_or(Error::MalformedToken)?;
+    let signature_segment = segments.next().ok_or(Error::MalformedToken)?;
+    if segments.next().is_some() {
+        return Err(Error::MalformedToken);
+    }
+
+    let header: TokenHeader = serde_json::from_slice(&crate::base64url::decode(header_segment)?)?;
+    let jwk = jwks
+        .keys
+        .iter()
+        .find(|jwk| jwk.kid == header.kid)
+        .ok_or(Error::UnknownKey)?;
+
+    let signature = crate::base64url::decode(signature_segment)?;
+    let signed_data_len = header_segment.len() + 1 + payload_segment.len();
+    let signed_data = &token.as_bytes()[..signed_data_len];
+
+    let public_key = ring::signature::RsaPublicKeyComponents {
+        n: &jwk.n,
+        e: &jwk.e,
+    };
+    public_key
+        .verify(
+            &ring::signature::RSA_PKCS1_2048_8192_SHA256,
+            signed_data,
+            &signature,
+        )
+        .map_err(|_| Error::InvalidSignature)?;
+
+    crate::base64url::decode(payload_segment).map_err(Error::from)
+}
+
+/// Fetches a [`JwkSet`] from its source (e.g. Apple's JWKS endpoint).
+///
+/// Implemented by the caller so this crate does not have to commit to a
+/// particular HTTP client.
+#[cfg(feature = "jwks-cache")]
+#[async_trait::async_trait]
+pub trait JwksFetcher {
+    /// The error returned when the fetch fails.
+    type Error;
+
+    /// Fetches the current `JwkSet`.
+    async fn fetch(&self) -> Result<JwkSet, Self::Error>;
+}
+
+/// Errors which can occur resolving a `kid` through a [`JwksCache`].
+#[derive(Debug)]
+#[non_exhaustive]
+#[cfg(feature = "jwks-cache")]
+pub enum CacheError<E> {
+    /// The underlying [`JwksFetcher`] failed.
+    Fetch(E),
+    /// `kid` was not present even after a refresh.
+    UnknownKey,
+}
+
+#[cfg(feature = "jwks-cache")]
+struct CacheState {
+    jwks: Option<JwkSet>,
+    fetched_at: Option<std::time::Instant>,
+}
+
+/// An in-memory cache of a [`JwksFetcher`]'s `JwkSet`, refreshed at most once
+/// per `ttl` on the hot path, plus once more on a `kid` that isn't cached
+/// (handling key rotation between refreshes).
+#[cfg(feature = "jwks-cache")]
+pub struct JwksCache<F> {
+    fetcher: F,
+    ttl: std::time::Duration,
+    state: std::sync::Mutex<CacheState>,
+}
+
+#[cfg(feature = "jwks-cache")]
+impl<F> JwksCache<F>
+where
+    F: JwksFetcher,
+{
+    /// Constructs a cache around `fetcher`, treating a fetched `JwkSet` as
+    /// fresh for `ttl`.
+    pub fn new(fetcher: F, ttl: std::time::Duration) -> Self {
+        JwksCache {
+            fetcher,
+            ttl,
+            state: std::sync::Mutex::new(CacheState {
+                jwks: None,
+                fetched_at: None,
+            }),
+        }
+    }
+
+    /// Returns the [`Jwk`] matching `kid`, refreshing the cache once if it is
+    /// stale or does not hold `kid`; if `kid` is still unknown after that
+    /// refresh, returns [`CacheError::UnknownKey`].
+    pub async fn get(&self, kid: &str) -> Result<Jwk, CacheError<F::Error>> {
+        if let Some(jwk) = self.cached(kid) {
+            return Ok(jwk);
+        }
+        self.refresh().await?;
+        self.cached(kid).ok_or(CacheError::UnknownKey)
+    }
+
+    async fn refresh(&self) -> Result<(), CacheError<F::Error>> {
+        let jwks = self.fetcher.fetch().await.map_err(CacheError::Fetch)?;
+        let mut state = self.state.lock().unwrap();
+        state.jwks = Some(jwks);
+        state.fetched_at = Some(std::time::Instant::now());
+        Ok(())
+    }
+
+    fn cached(&self, kid: &str) -> Option<Jwk> {
+        let state = self.state.lock().unwrap();
+        let fetched_at = state.fetched_at?;
+        if fetched_at.elapsed() > self.ttl {
+            return None;
+        }
+        state.jwks.as_ref()?.keys.iter().find(|jwk| jwk.kid == kid).cloned()
+    }
+}
+
+#[cfg(all(test, feature = "jwks-cache"))]
+mod tests {
+    use super::{CacheError, Jwk, JwkSet, JwksCache, JwksFetcher};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+    use std::time::Duration;
+
+    fn jwk(kid: &str) -> Jwk {
+        Jwk {
+            kty: String::from("RSA"),
+            kid: String::from(kid),
+            alg: Some(String::from("RS256")),
+            n: vec![1, 2, 3],
+            e: vec![1, 0, 1],
+        }
+    }
+
+    // None of the `JwksFetcher` impls under test ever await anything, so a
+    // no-op waker is enough to drive the futures `async_trait` generates to
+    // completion without pulling in an executor crate.
+    fn noop_waker() -> Waker {
+        fn clone(_: *const ()) -> RawWaker {
+            raw_waker()
+        }
+        fn noop(_: *const ()) {}
+        fn raw_waker() -> RawWaker {
+            const VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+            RawWaker::new(std::ptr::null(), &VTABLE)
+        }
+        unsafe { Waker::from_raw(raw_waker()) }
+    }
+
+    fn block_on<F: std::future::Future>(mut future: F) -> F::Output {
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        // Safety: `future` is not moved again after being pinned here.
+        let mut future = unsafe { std::pin::Pin::new_unchecked(&mut future) };
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    struct CountingFetcher {
+        jwks: JwkSet,
+        fetch_count: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl JwksFetcher for CountingFetcher {
+        type Error = ();
+
+        async fn fetch(&self) -> Result<JwkSet, Self::Error> {
+            self.fetch_count.fetch_add(1, Ordering::SeqCst);
+            Ok(self.jwks.clone())
+        }
+    }
+
+    #[test]
+    fn get_refreshes_once_on_a_cold_cache_then_serves_from_cache() {
+        let fetcher = CountingFetcher {
+            jwks: JwkSet {
+                keys: vec![jwk("kid-1")],
+            },
+            fetch_count: AtomicUsize::new(0),
+        };
+        let cache = JwksCache::new(fetcher, Duration::from_secs(3600));
+
+        let first = block_on(cache.get("kid-1")).unwrap();
+        assert_eq!(first.kid, "kid-1");
+        assert_eq!(cache.fetcher.fetch_count.load(Ordering::SeqCst), 1);
+
+        let second = block_on(cache.get("kid-1")).unwrap();
+        assert_eq!(second.kid, "kid-1");
+        assert_eq!(cache.fetcher.fetch_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn get_refreshes_once_more_on_a_kid_miss_after_key_rotation() {
+        let fetcher = CountingFetcher {
+            jwks: JwkSet {
+                keys: vec![jwk("kid-1")],
+            },
+            fetch_count: AtomicUsize::new(0),
+        };
+        let cache = JwksCache::new(fetcher, Duration::from_secs(3600));
+
+        block_on(cache.get("kid-1")).unwrap();
+        assert_eq!(cache.fetcher.fetch_count.load(Ordering::SeqCst), 1);
+
+        // "kid-2" isn't in the cached set, so `get` should refresh once more
+        // before giving up.
+        let result = block_on(cache.get("kid-2"));
+        assert!(matches!(result, Err(CacheError::UnknownKey)));
+        assert_eq!(cache.fetcher.fetch_count.load(Ordering::SeqCst), 2);
+    }
+
+    struct FailingFetcher;
+
+    #[async_trait::async_trait]
+    impl JwksFetcher for FailingFetcher {
+        type Error = &'static str;
+
+        async fn fetch(&self) -> Result<JwkSet, Self::Error> {
+            Err("network error")
+        }
+    }
+
+    #[test]
+    fn get_propagates_the_fetcher_error() {
+        let cache = JwksCache::new(FailingFetcher, Duration::from_secs(3600));
+        let result = block_on(cache.get("kid-1"));
+        assert!(matches!(result, Err(CacheError::Fetch("network error"))));
+    }
+
+    struct MutableFetcher {
+        jwks: Mutex<JwkSet>,
+        fetch_count: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl JwksFetcher for MutableFetcher {
+        type Error = ();
+
+        async fn fetch(&self) -> Result<JwkSet, Self::Error> {
+            self.fetch_count.fetch_add(1, Ordering::SeqCst);
+            Ok(self.jwks.lock().unwrap().clone())
+        }
+    }
+
+    #[test]
+    fn get_refreshes_again_once_the_ttl_has_elapsed() {
+        let fetcher = MutableFetcher {
+            jwks: Mutex::new(JwkSet {
+                keys: vec![jwk("kid-1")],
+            }),
+            fetch_count: AtomicUsize::new(0),
+        };
+        let ttl = Duration::from_millis(20);
+        let cache = JwksCache::new(fetcher, ttl);
+
+        block_on(cache.get("kid-1")).unwrap();
+        assert_eq!(cache.fetcher.fetch_count.load(Ordering::SeqCst), 1);
+
+        // Still within `ttl`: served from cache, no second fetch.
+        block_on(cache.get("kid-1")).unwrap();
+        assert_eq!(cache.fetcher.fetch_count.load(Ordering::SeqCst), 1);
+
+        *cache.fetcher.jwks.lock().unwrap() = JwkSet {
+            keys: vec![jwk("kid-2")],
+        };
+        std::thread::sleep(ttl * 2);
+
+        // Past `ttl`, the stale entry is refreshed and the rotated key picked up.
+        let refreshed = block_on(cache.get("kid-2")).unwrap();
+        assert_eq!(refreshed.kid, "kid-2");
+        assert_eq!(cache.fetcher.fetch_count.load(Ordering::SeqCst), 2);
+    }
+}
@@ -39,6 +39,18 @@ impl Env {
         s.push_str("/v1/validate_device_token");
         s
     }
+
+    pub fn query_two_bits_endpoint(self) -> String {
+        let mut s = self.base_endpoint();
+        s.push_str("/v1/query_two_bits");
+        s
+    }
+
+    pub fn update_two_bits_endpoint(self) -> String {
+        let mut s = self.base_endpoint();
+        s.push_str("/v1/update_two_bits");
+        s
+    }
 }
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
@@ -61,6 +73,64 @@ impl ValidationReq {
     }
 }
 
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct QueryTwoBitsReq {
+    device_token: String,
+    transaction_id: String,
+    timestamp: u64,
+}
+
+impl QueryTwoBitsReq {
+    pub fn new<T>(device_token: &str, transaction_id: &str, duration_since_epoch: T) -> Self
+    where
+        T: DurationSinceEpoch,
+    {
+        QueryTwoBitsReq {
+            device_token: String::from(device_token),
+            transaction_id: String::from(transaction_id),
+            timestamp: duration_since_epoch.as_millis(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct QueryTwoBitsResp {
+    pub bit0: bool,
+    pub bit1: bool,
+    /// The server month the bits were last updated, in `YYYY-MM` form.
+    pub last_update_time: Option<String>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct UpdateTwoBitsReq {
+    device_token: String,
+    transaction_id: String,
+    timestamp: u64,
+    bit0: bool,
+    bit1: bool,
+}
+
+impl UpdateTwoBitsReq {
+    pub fn new<T>(
+        device_token: &str,
+        transaction_id: &str,
+        duration_since_epoch: T,
+        bit0: bool,
+        bit1: bool,
+    ) -> Self
+    where
+        T: DurationSinceEpoch,
+    {
+        UpdateTwoBitsReq {
+            device_token: String::from(device_token),
+            transaction_id: String::from(transaction_id),
+            timestamp: duration_since_epoch.as_millis(),
+            bit0,
+            bit1,
+        }
+    }
+}
+
 // #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 // pub enum VerifyDeviceTokenResult {
 //     Verified,
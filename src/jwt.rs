@@ -30,6 +30,8 @@ use alloc::vec::Vec;
 #[cfg(feature = "std")]
 use std::vec::Vec;
 
+#[cfg(feature = "std")]
+use crate::base64url;
 use crate::{time::DurationSinceEpoch, TeamId};
 use serde::{Deserialize, Serialize};
 
@@ -140,17 +142,58 @@ impl<'a> Header<'a> {
     }
 }
 
+/// A claim value that is either a single string or an array of strings, as
+/// RFC 7519 §4.1.3 permits for `aud`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum OneOrMany<'a> {
+    One(&'a str),
+    Many(Vec<&'a str>),
+}
+
+impl<'a> Serialize for OneOrMany<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            OneOrMany::One(s) => serializer.serialize_str(s),
+            OneOrMany::Many(v) => v.serialize(serializer),
+        }
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for OneOrMany<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr<'a> {
+            One(&'a str),
+            Many(Vec<&'a str>),
+        }
+
+        Repr::deserialize(deserializer).map(|repr| match repr {
+            Repr::One(s) => OneOrMany::One(s),
+            Repr::Many(v) => OneOrMany::Many(v),
+        })
+    }
+}
+
 /// Contains the issuer ID (team ID), when the token was issued, and when the token expires.
-#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub struct Claims<'a> {
     pub iss: &'a str,
     pub iat: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub exp: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub aud: Option<&'a str>,
+    pub aud: Option<OneOrMany<'a>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sub: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<u64>,
 }
 
 impl<'a> Claims<'a> {
@@ -165,6 +208,7 @@ impl<'a> Claims<'a> {
             exp: None,
             aud: None,
             sub: None,
+            nbf: None,
         }
     }
 
@@ -183,15 +227,198 @@ impl<'a> Claims<'a> {
         self.exp
     }
 
-    /// Returns the intended audience.
-    pub fn aud(&self) -> Option<&str> {
-        self.aud
+    /// Returns the intended audience(s).
+    pub fn aud(&self) -> Option<&OneOrMany<'a>> {
+        self.aud.as_ref()
     }
 
     /// Returns the subject.
     pub fn sub(&self) -> Option<&str> {
         self.sub
     }
+
+    /// Returns when the token becomes valid, as the number of seconds since
+    /// the Unix epoch.
+    pub fn nbf(&self) -> Option<u64> {
+        self.nbf
+    }
+
+    /// Validates `exp`/`nbf`/`iat` against `now`, allowing `leeway_secs` of
+    /// clock skew between the issuer and this host.
+    pub fn validate(
+        &self,
+        now: &impl DurationSinceEpoch,
+        leeway_secs: u64,
+    ) -> Result<(), ValidationError> {
+        let now_secs = now.as_secs();
+
+        if let Some(nbf) = self.nbf {
+            if now_secs + leeway_secs < nbf {
+                return Err(ValidationError::NotYetValid);
+            }
+        }
+
+        if let Some(exp) = self.exp {
+            if now_secs.saturating_sub(leeway_secs) > exp {
+                return Err(ValidationError::Expired);
+            }
+        }
+
+        if self.iat > now_secs + leeway_secs {
+            return Err(ValidationError::IssuedInFuture);
+        }
+
+        Ok(())
+    }
+}
+
+/// The reason [`Claims::validate`] rejected a token.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ValidationError {
+    /// `now - leeway_secs > exp`.
+    Expired,
+    /// `now + leeway_secs < nbf`.
+    NotYetValid,
+    /// `iat` is further in the future than `leeway_secs` allows.
+    IssuedInFuture,
+}
+
+/// Errors which can occur while encoding a JWT.
+#[derive(Debug)]
+#[non_exhaustive]
+#[cfg(feature = "std")]
+pub enum Error {
+    /// The header or claims could not be serialized to JSON.
+    Json(serde_json::Error),
+    /// The signing key could not be parsed.
+    Key(ring::error::KeyRejected),
+    /// The signing operation failed.
+    Signing(ring::error::Unspecified),
+    /// The combination of `KeyData` and `Algorithm` is not supported.
+    UnsupportedKeyData,
+}
+
+#[cfg(feature = "std")]
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<ring::error::KeyRejected> for Error {
+    fn from(err: ring::error::KeyRejected) -> Self {
+        Error::Key(err)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<ring::error::Unspecified> for Error {
+    fn from(err: ring::error::Unspecified) -> Self {
+        Error::Signing(err)
+    }
+}
+
+/// Builds the compact JWS serialization for `claims`, signed with `key`.
+///
+/// The signing input is `base64url(header) + "." + base64url(claims)`. For
+/// [`Algorithm::Es256`], the signature is computed with ECDSA P-256/SHA-256 and
+/// emitted in the JOSE fixed `r||s` form (see
+/// [`crate::crypto::ecdsa::EcdsaP256Sha256FixedFormat`]), *not* the ASN.1 DER
+/// form, since Apple's services reject DER-wrapped signatures.
+#[cfg(feature = "std")]
+pub fn encode(key: &Key, claims: &Claims) -> Result<String, Error> {
+    let header = Header::new(key);
+
+    let mut signing_input = base64url::encode(&serde_json::to_vec(&header)?);
+    signing_input.push('.');
+    signing_input.push_str(&base64url::encode(&serde_json::to_vec(claims)?));
+
+    let signature = match (key.alg(), key.data()) {
+        (Algorithm::Es256, KeyData::Pkcs8(pkcs8)) => sign_es256(pkcs8, signing_input.as_bytes())?,
+        (Algorithm::Rs256, KeyData::Pkcs8(pkcs8)) => sign_rs256(pkcs8, signing_input.as_bytes())?,
+        (_, KeyData::Jwk(_)) => return Err(Error::UnsupportedKeyData),
+    };
+
+    signing_input.push('.');
+    signing_input.push_str(&base64url::encode(&signature));
+
+    Ok(signing_input)
+}
+
+#[cfg(feature = "std")]
+fn sign_es256(pkcs8: &[u8], signing_input: &[u8]) -> Result<Vec<u8>, Error> {
+    let rng = ring::rand::SystemRandom::new();
+    let key_pair = ring::signature::EcdsaKeyPair::from_pkcs8(
+        &ring::signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+        pkcs8,
+    )?;
+    let signature = key_pair.sign(&rng, signing_input)?;
+    Ok(Vec::from(signature.as_ref()))
+}
+
+#[cfg(feature = "std")]
+fn sign_rs256(pkcs8: &[u8], signing_input: &[u8]) -> Result<Vec<u8>, Error> {
+    let key_pair = ring::signature::RsaKeyPair::from_pkcs8(pkcs8)?;
+    let rng = ring::rand::SystemRandom::new();
+    let mut signature = vec![0u8; key_pair.public_modulus_len()];
+    key_pair.sign(
+        &ring::signature::RSA_PKCS1_SHA256,
+        &rng,
+        signing_input,
+        &mut signature,
+    )?;
+    Ok(signature)
+}
+
+/// Wraps a PKCS#1 RSA private key (DER) in the PKCS#8 envelope that
+/// [`ring::signature::RsaKeyPair::from_pkcs8`] requires.
+///
+/// Many RSA keys (e.g. those produced by `openssl genrsa`) are distributed in
+/// PKCS#1 form rather than PKCS#8. This performs the same bridging other
+/// crates built on ring/jsonwebtoken do before handing the key to the
+/// backend, so a raw PKCS#1 key can still be passed to [`encode`] via
+/// [`KeyData::Pkcs8`].
+#[cfg(feature = "std")]
+pub fn rsa_pkcs1_to_pkcs8(pkcs1_der: &[u8]) -> Vec<u8> {
+    // PKCS#8 PrivateKeyInfo ::= SEQUENCE {
+    //   version                 INTEGER (0),
+    //   privateKeyAlgorithm     SEQUENCE { rsaEncryption OID, NULL },
+    //   privateKey              OCTET STRING (the PKCS#1 bytes),
+    // }
+    const RSA_ENCRYPTION_ALGORITHM: &[u8] = &[
+        0x30, 0x0d, 0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01, 0x05, 0x00,
+    ];
+
+    let mut private_key = Vec::with_capacity(pkcs1_der.len() + 4);
+    private_key.push(0x04);
+    push_der_len(&mut private_key, pkcs1_der.len());
+    private_key.extend_from_slice(pkcs1_der);
+
+    let mut body = Vec::with_capacity(3 + RSA_ENCRYPTION_ALGORITHM.len() + private_key.len());
+    body.extend_from_slice(&[0x02, 0x01, 0x00]);
+    body.extend_from_slice(RSA_ENCRYPTION_ALGORITHM);
+    body.extend_from_slice(&private_key);
+
+    let mut der = Vec::with_capacity(body.len() + 4);
+    der.push(0x30);
+    push_der_len(&mut der, body.len());
+    der.extend_from_slice(&body);
+    der
+}
+
+#[cfg(feature = "std")]
+fn push_der_len(out: &mut Vec<u8>, len: usize) {
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let first_nonzero = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len() - 1);
+        let len_bytes = &bytes[first_nonzero..];
+        out.push(0x80 | len_bytes.len() as u8);
+        out.extend_from_slice(len_bytes);
+    }
 }
 
 /// A JSON Web Key.
@@ -235,3 +462,170 @@ pub struct Jwk {
 pub struct JwkSet {
     pub keys: Vec<Jwk>,
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::{
+        encode, rsa_pkcs1_to_pkcs8, Algorithm, Claims, Key, KeyData, KeyId, OneOrMany,
+        ValidationError,
+    };
+    use base64::Engine as _;
+
+    #[test]
+    fn encode_then_verify_es256_round_trip() {
+        // A P-256 PKCS#8 private key and its matching uncompressed public
+        // point, generated for this test only.
+        const PKCS8_B64: &str = "MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQg1RzCRq1Q50giq/rhMQ5XNillOlLCMsZKPr7nKczJUpChRANCAASeN2TiMUzo62BW8yEyVl06h2ILD2fLzLqREbcjOH6gxdVAScReKZve+Bb+XrtBNEvJDMi6Jk0y2JeEJrKMCMZ4";
+        const PUB_POINT_B64: &str = "BJ43ZOIxTOjrYFbzITJWXTqHYgsPZ8vMupERtyM4fqDF1UBJxF4pm974Fv5eu0E0S8kMyLomTTLYl4QmsowIxng=";
+
+        let pkcs8 = base64::engine::general_purpose::STANDARD
+            .decode(PKCS8_B64)
+            .unwrap();
+        let pub_point = base64::engine::general_purpose::STANDARD
+            .decode(PUB_POINT_B64)
+            .unwrap();
+
+        let team_id = crate::TeamId("TEAMID1234");
+        let key_id = KeyId("KEYID1");
+        let key_data = KeyData::Pkcs8(&pkcs8);
+        let key = Key::new(&key_id, Algorithm::Es256, &key_data);
+        let claims = Claims::new(&team_id, crate::time::StdDurationSinceEpoch::now());
+
+        let token = encode(&key, &claims).unwrap();
+
+        let mut parts = token.split('.');
+        let header_b64 = parts.next().unwrap();
+        let claims_b64 = parts.next().unwrap();
+        let sig_b64 = parts.next().unwrap();
+        assert!(parts.next().is_none());
+
+        let signed_data_len = header_b64.len() + 1 + claims_b64.len();
+        let signed_data = &token.as_bytes()[..signed_data_len];
+        let signature = crate::base64url::decode(sig_b64).unwrap();
+
+        let public_key = ring::signature::UnparsedPublicKey::new(
+            &ring::signature::ECDSA_P256_SHA256_FIXED,
+            &pub_point,
+        );
+        public_key.verify(signed_data, &signature).unwrap();
+
+        let claims_bytes = crate::base64url::decode(claims_b64).unwrap();
+        let decoded_claims: Claims = serde_json::from_slice(&claims_bytes).unwrap();
+        assert_eq!(decoded_claims.iss, "TEAMID1234");
+    }
+
+    const RSA_ENCRYPTION_ALGORITHM: &[u8] = &[
+        0x30, 0x0d, 0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01, 0x05, 0x00,
+    ];
+
+    #[test]
+    fn rsa_pkcs1_to_pkcs8_short_key_uses_single_byte_der_lengths() {
+        let pkcs1 = [0xAAu8; 10];
+
+        let mut expected = vec![0x30, 0x1E, 0x02, 0x01, 0x00];
+        expected.extend_from_slice(RSA_ENCRYPTION_ALGORITHM);
+        expected.push(0x04);
+        expected.push(0x0A);
+        expected.extend_from_slice(&pkcs1);
+
+        assert_eq!(rsa_pkcs1_to_pkcs8(&pkcs1), expected);
+    }
+
+    #[test]
+    fn rsa_pkcs1_to_pkcs8_long_key_uses_long_form_der_lengths() {
+        let pkcs1 = [0xBBu8; 200];
+
+        let mut expected = vec![0x30, 0x81, 0xDD, 0x02, 0x01, 0x00];
+        expected.extend_from_slice(RSA_ENCRYPTION_ALGORITHM);
+        expected.push(0x04);
+        expected.push(0x81);
+        expected.push(0xC8);
+        expected.extend_from_slice(&pkcs1);
+
+        assert_eq!(rsa_pkcs1_to_pkcs8(&pkcs1), expected);
+    }
+
+    struct FixedTime(u64);
+
+    impl crate::time::DurationSinceEpoch for FixedTime {
+        fn as_secs(&self) -> u64 {
+            self.0
+        }
+
+        fn as_millis(&self) -> u64 {
+            self.0 * 1000
+        }
+    }
+
+    const TEAM_ID: crate::TeamId<'static> = crate::TeamId("TEAM");
+
+    fn claims_at(iat: u64, exp: Option<u64>, nbf: Option<u64>) -> Claims<'static> {
+        let mut claims = Claims::new(&TEAM_ID, FixedTime(iat));
+        claims.exp = exp;
+        claims.nbf = nbf;
+        claims
+    }
+
+    #[test]
+    fn validate_accepts_claims_within_bounds() {
+        let claims = claims_at(100, Some(200), Some(50));
+        assert!(claims.validate(&FixedTime(150), 0).is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_expired_claims() {
+        let claims = claims_at(100, Some(200), None);
+        assert_eq!(
+            claims.validate(&FixedTime(201), 0),
+            Err(ValidationError::Expired)
+        );
+    }
+
+    #[test]
+    fn validate_allows_expired_claims_within_leeway() {
+        let claims = claims_at(100, Some(200), None);
+        assert!(claims.validate(&FixedTime(205), 10).is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_not_yet_valid_claims() {
+        let claims = claims_at(100, None, Some(300));
+        assert_eq!(
+            claims.validate(&FixedTime(299), 0),
+            Err(ValidationError::NotYetValid)
+        );
+    }
+
+    #[test]
+    fn validate_allows_not_yet_valid_claims_within_leeway() {
+        let claims = claims_at(100, None, Some(300));
+        assert!(claims.validate(&FixedTime(295), 10).is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_claims_issued_in_the_future() {
+        let claims = claims_at(1_000, None, None);
+        assert_eq!(
+            claims.validate(&FixedTime(900), 0),
+            Err(ValidationError::IssuedInFuture)
+        );
+    }
+
+    #[test]
+    fn one_or_many_round_trips_a_single_audience() {
+        let value = OneOrMany::One("https://example.com");
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, "\"https://example.com\"");
+        let decoded: OneOrMany = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, value);
+    }
+
+    #[test]
+    fn one_or_many_round_trips_multiple_audiences() {
+        let value = OneOrMany::Many(vec!["https://a.example", "https://b.example"]);
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, "[\"https://a.example\",\"https://b.example\"]");
+        let decoded: OneOrMany = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, value);
+    }
+}
@@ -13,11 +13,17 @@
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
+#[cfg(feature = "std")]
+pub mod app_attest;
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub(crate) mod base64url;
 pub mod cloudkit;
 pub mod crypto;
 #[cfg(any(feature = "alloc", feature = "std"))]
 pub mod device_check;
 #[cfg(any(feature = "alloc", feature = "std"))]
+pub mod jwt;
+#[cfg(any(feature = "alloc", feature = "std"))]
 pub mod siwa;
 pub mod time;
 
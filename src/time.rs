@@ -49,3 +49,36 @@ impl DurationSinceEpoch for StdDurationSinceEpoch {
         self.0.as_secs() * 1000 + u64::from(self.0.subsec_millis())
     }
 }
+
+/// Implements [`DurationSinceEpoch`] for `chrono::DateTime<Utc>` directly, so
+/// an application already working in `chrono` does not have to convert by
+/// hand (and risk a sign/overflow mistake doing so).
+///
+/// A timestamp before the Unix epoch saturates to `0` rather than wrapping,
+/// since claim timestamps are never meaningfully negative.
+#[cfg(feature = "chrono")]
+impl DurationSinceEpoch for chrono::DateTime<chrono::Utc> {
+    fn as_secs(&self) -> u64 {
+        self.timestamp().max(0) as u64
+    }
+
+    fn as_millis(&self) -> u64 {
+        self.timestamp_millis().max(0) as u64
+    }
+}
+
+/// Implements [`DurationSinceEpoch`] for `time::OffsetDateTime` directly, so
+/// an application already working in the `time` crate does not have to
+/// convert by hand.
+///
+/// A timestamp before the Unix epoch saturates to `0` rather than wrapping.
+#[cfg(feature = "time")]
+impl DurationSinceEpoch for time::OffsetDateTime {
+    fn as_secs(&self) -> u64 {
+        self.unix_timestamp().max(0) as u64
+    }
+
+    fn as_millis(&self) -> u64 {
+        (self.unix_timestamp_nanos() / 1_000_000).max(0) as u64
+    }
+}